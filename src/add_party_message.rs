@@ -12,6 +12,8 @@
 
 use crate::error::{FsDkrError, FsDkrResult};
 use crate::refresh_message::RefreshMessage;
+use crate::ring_pedersen_proof::{RingPedersenProof, RingPedersenStatement};
+use crate::zeroize_support::Sensitive;
 use curv::arithmetic::{BasicOps, Modulo, One, Samplable, Zero};
 use curv::cryptographic_primitives::hashing::{Digest, DigestExt};
 use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
@@ -30,48 +32,59 @@ use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement, NiCorrectKeyProof
 
 /// Message used by new parties to join the protocol.
 #[derive(Clone, Deserialize, Serialize, Debug)]
-pub struct JoinMessage {
+pub struct JoinMessage<E: Curve, H: Digest + Clone> {
     pub(crate) ek: EncryptionKey,
     pub(crate) dk_correctness_proof: NiCorrectKeyProof,
     pub(crate) party_index: Option<u16>,
     pub(crate) dlog_statement: DLogStatement,
     pub(crate) composite_dlog_proof_base_h1: CompositeDLogProof,
     pub(crate) composite_dlog_proof_base_h2: CompositeDLogProof,
-}
-
-fn generate pedersen_parameters() -> () {
-    let (ek_tilde, dk_tilde) = Paillier::keypair_with_modulus_size(crate::PAILLIER_KEY_SIZE).keys();
-    let one = BigInt::one();
-    let phi = (&dk_tilde.p - &one) * (&dk_tilde.q - &one);
-    let s = BigInt::sample_below(&ek_tilde.n);
-    let t = BigInt::mod_pow(&h1, &xhi, &ek_tilde.n);
-    ()
+    pub(crate) ring_pedersen_statement: RingPedersenStatement<E, H>,
+    pub(crate) ring_pedersen_proof: RingPedersenProof<E, H>,
 }
 
 /// Generates the parameters needed for the h1_h2_N_tilde_vec. These parameters can be seen as
 /// environment variables for each party that they agree on. In this case, each new party generates
 /// it's own DlogStatements and submits it's proofs
-fn generate_h1_h2_n_tilde() -> (BigInt, BigInt, BigInt, BigInt, BigInt) {
+///
+/// Returns `(N_tilde, h1, h2, x0, xhi, xhi_inv, phi)`: `x0` is the actual discrete log of `h2`
+/// base `h1` (`h2 = h1^x0 mod N_tilde`), needed as-is by anything that checks that relation
+/// directly (e.g. [RingPedersenProof]); `xhi`/`xhi_inv` are `phi - x0`/`phi - x0^-1`, the negated
+/// exponents [CompositeDLogProof] expects under its own sign convention.
+pub(crate) fn generate_h1_h2_n_tilde() -> (BigInt, BigInt, BigInt, BigInt, BigInt, BigInt, BigInt) {
     let (ek_tilde, dk_tilde) = Paillier::keypair_with_modulus_size(crate::PAILLIER_KEY_SIZE).keys();
     let one = BigInt::one();
     let phi = (&dk_tilde.p - &one) * (&dk_tilde.q - &one);
     let h1 = BigInt::sample_below(&ek_tilde.n);
-    let (mut xhi, mut xhi_inv) = loop {
-        let xhi_ = BigInt::sample_below(&phi);
-        match BigInt::mod_inv(&xhi_, &phi) {
-            Some(inv) => break (xhi_, inv),
+    let (x0, x0_inv) = loop {
+        let x0_ = BigInt::sample_below(&phi);
+        match BigInt::mod_inv(&x0_, &phi) {
+            Some(inv) => break (x0_, inv),
             None => continue,
         }
     };
-    let h2 = BigInt::mod_pow(&h1, &xhi, &ek_tilde.n);
-    xhi = BigInt::sub(&phi, &xhi);
-    xhi_inv = BigInt::sub(&phi, &xhi_inv);
-    (ek_tilde.n, h1, h2, xhi, xhi_inv)
+    let h2 = BigInt::mod_pow(&h1, &x0, &ek_tilde.n);
+    let xhi = BigInt::sub(&phi, &x0);
+    let xhi_inv = BigInt::sub(&phi, &x0_inv);
+    (ek_tilde.n, h1, h2, x0, xhi, xhi_inv, phi)
 }
 
-/// Generates the DlogStatement and CompositeProofs using the parameters generated by [generate_h1_h2_n_tilde]
-fn generate_dlog_statement_proofs() -> (DLogStatement, CompositeDLogProof, CompositeDLogProof) {
-    let (n_tilde, h1, h2, xhi, xhi_inv) = generate_h1_h2_n_tilde();
+/// Generates the DlogStatement, CompositeProofs and the ring-Pedersen (Π^prm) proof using the
+/// parameters generated by [generate_h1_h2_n_tilde]. The ring-Pedersen proof binds to the same
+/// `(N_tilde, h1, h2)` as the two composite dlog proofs, so a verifier can use either (or both)
+/// to check that the Pedersen parameters were generated honestly.
+pub(crate) fn generate_dlog_statement_proofs<E, H>() -> (
+    DLogStatement,
+    CompositeDLogProof,
+    CompositeDLogProof,
+    RingPedersenStatement<E, H>,
+    RingPedersenProof<E, H>,
+)
+where
+    E: Curve,
+    H: Digest + Clone,
+{
+    let (n_tilde, h1, h2, x0, xhi, xhi_inv, phi) = generate_h1_h2_n_tilde();
 
     let dlog_statement_base_h1 = DLogStatement {
         N: n_tilde.clone(),
@@ -80,22 +93,27 @@ fn generate_dlog_statement_proofs() -> (DLogStatement, CompositeDLogProof, Compo
     };
 
     let dlog_statement_base_h2 = DLogStatement {
-        N: n_tilde,
-        g: h2,
-        ni: h1,
+        N: n_tilde.clone(),
+        g: h2.clone(),
+        ni: h1.clone(),
     };
 
     let composite_dlog_proof_base_h1 = CompositeDLogProof::prove(&dlog_statement_base_h1, &xhi);
     let composite_dlog_proof_base_h2 = CompositeDLogProof::prove(&dlog_statement_base_h2, &xhi_inv);
 
+    let ring_pedersen_statement = RingPedersenStatement::<E, H>::from_parts(n_tilde, h1, h2, phi);
+    let ring_pedersen_proof = RingPedersenProof::<E, H>::prove_with_exponent(&x0, &ring_pedersen_statement);
+
     (
         dlog_statement_base_h1,
         composite_dlog_proof_base_h1,
         composite_dlog_proof_base_h2,
+        ring_pedersen_statement,
+        ring_pedersen_proof,
     )
 }
 
-impl JoinMessage {
+impl<E: Curve, H: Digest + Clone> JoinMessage<E, H> {
     pub fn set_party_index(&mut self, new_party_index: u16) {
         self.party_index = Some(new_party_index);
     }
@@ -104,8 +122,13 @@ impl JoinMessage {
     /// [Keys] that are going to be used when generating the [LocalKey].
     pub fn distribute() -> (Self, Keys) {
         let paillier_key_pair = Keys::create(0);
-        let (dlog_statement, composite_dlog_proof_base_h1, composite_dlog_proof_base_h2) =
-            generate_dlog_statement_proofs();
+        let (
+            dlog_statement,
+            composite_dlog_proof_base_h1,
+            composite_dlog_proof_base_h2,
+            ring_pedersen_statement,
+            ring_pedersen_proof,
+        ) = generate_dlog_statement_proofs::<E, H>();
 
         let join_message = JoinMessage {
             // in a join message, we only care about the ek and the correctness proof
@@ -114,6 +137,8 @@ impl JoinMessage {
             dlog_statement,
             composite_dlog_proof_base_h1,
             composite_dlog_proof_base_h2,
+            ring_pedersen_statement,
+            ring_pedersen_proof,
             party_index: None,
         };
 
@@ -130,61 +155,140 @@ impl JoinMessage {
     /// tailored for a sent JoinMessage on which we assigned party_index. In this collect, a [LocalKey]
     /// is filled with the information provided by the [RefreshMessage]s from the other parties and
     /// the other join messages (multiple parties can be added/replaced at once).
-    pub fn collect<E, H>(
+    ///
+    /// `old_t`/`old_n` are the quorum the group is resharing *from* (used only to Lagrange-
+    /// reconstruct the previous secret from the incoming [RefreshMessage]s); `new_t`/`new_n` are
+    /// the quorum it is resharing *to*, i.e. the degree/size of the resulting [LocalKey]. Passing
+    /// `new_t == old_t` and `new_n == old_n` is a plain refresh with no threshold change.
+    #[allow(clippy::too_many_arguments)]
+    pub fn collect(
         &self,
         refresh_messages: &[RefreshMessage<E, H>],
         paillier_key: Keys,
-        join_messages: &[JoinMessage],
-        t: u16,
-        n: u16,
-    ) -> FsDkrResult<LocalKey<E>>
-    where
-        E: Curve,
-        H: Digest + Clone,
-    {
-        RefreshMessage::validate_collect(refresh_messages, t, n)?;
+        join_messages: &[JoinMessage<E, H>],
+        old_t: u16,
+        old_n: u16,
+        new_t: u16,
+        new_n: u16,
+    ) -> FsDkrResult<LocalKey<E>> {
+        RefreshMessage::validate_collect(refresh_messages, old_t, old_n)?;
 
         // check if a party_index has been assigned to the current party
         let party_index = self.get_party_index()?;
 
-        // check if a party_index has been assigned to all other new parties
-        // TODO: Check if no party_index collision exists
+        // check if a party_index has been assigned to all other new parties, and that their
+        // ring-Pedersen parameters are honestly generated before we rely on them below.
+        let mut party_index_counts: HashMap<u16, u16> = HashMap::new();
+        *party_index_counts.entry(party_index).or_insert(0) += 1;
+        for refresh_message in refresh_messages.iter() {
+            *party_index_counts.entry(refresh_message.party_index).or_insert(0) += 1;
+        }
         for join_message in join_messages.iter() {
-            join_message.get_party_index()?;
+            let other_party_index = join_message.get_party_index()?;
+            *party_index_counts.entry(other_party_index).or_insert(0) += 1;
+
+            if NiCorrectKeyProof::verify(&join_message.dk_correctness_proof, &join_message.ek, None)
+                .is_err()
+            {
+                return Err(FsDkrError::PaillierKeyError {
+                    party_index: other_party_index,
+                });
+            }
+
+            // The base-h2 proof was produced for the swapped statement (g = h2, ni = h1).
+            let dlog_statement_base_h2 = DLogStatement {
+                N: join_message.dlog_statement.N.clone(),
+                g: join_message.dlog_statement.ni.clone(),
+                ni: join_message.dlog_statement.g.clone(),
+            };
+            if CompositeDLogProof::verify(
+                &join_message.composite_dlog_proof_base_h1,
+                &join_message.dlog_statement,
+            )
+            .is_err()
+                || CompositeDLogProof::verify(
+                    &join_message.composite_dlog_proof_base_h2,
+                    &dlog_statement_base_h2,
+                )
+                .is_err()
+            {
+                return Err(FsDkrError::DlogProofValidationError {
+                    party_index: other_party_index,
+                });
+            }
+
+            RingPedersenProof::verify(
+                &join_message.ring_pedersen_proof,
+                &join_message.ring_pedersen_statement,
+                other_party_index,
+            )?;
         }
 
-        let parameters = ShamirSecretSharing {
-            threshold: t,
-            share_count: n,
+        // report every party_index that was claimed by more than one party, rather than
+        // silently letting the later entry win in `available_parties` below.
+        let mut colliding_indices: Vec<u16> = party_index_counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(index, _)| index)
+            .collect();
+        if !colliding_indices.is_empty() {
+            colliding_indices.sort_unstable();
+            return Err(FsDkrError::PartyIndexCollisionError {
+                party_indices: colliding_indices,
+            });
+        }
+
+        let old_parameters = ShamirSecretSharing {
+            threshold: old_t,
+            share_count: old_n,
         };
 
         // generate a new share, the details can be found here https://hackmd.io/@omershlo/Hy1jBo6JY.
+        // The decrypted share and the scalar derived from it are wrapped in `Sensitive`, which
+        // overwrites each with its zero value on drop; heap memory the old value owned (a
+        // `BigInt`'s digits) is freed without being overwritten.
         let (cipher_text_sum, li_vec) = RefreshMessage::get_ciphertext_sum(
             refresh_messages,
             party_index,
-            &parameters,
+            &old_parameters,
             &paillier_key.ek,
         );
-        let new_share = Paillier::decrypt(&paillier_key.dk, cipher_text_sum)
-            .0
-            .into_owned();
+        let cipher_text_sum = Sensitive::new(cipher_text_sum, BigInt::zero);
+        let new_share = Sensitive::new(
+            Paillier::decrypt(
+                &paillier_key.dk,
+                paillier::RawCiphertext::from(cipher_text_sum.get().clone()),
+            )
+                .0
+                .into_owned(),
+            BigInt::zero,
+        );
 
-        let new_share_fe: Scalar<E> = Scalar::<E>::from(&new_share);
+        let new_share_fe: Sensitive<Scalar<E>> =
+            Sensitive::new(Scalar::<E>::from(new_share.get()), Scalar::<E>::zero);
         let paillier_dk = paillier_key.dk.clone();
-        let key_linear_x_i = new_share_fe.clone();
-        let key_linear_y = Point::<E>::generator() * new_share_fe.clone();
+        let key_linear_x_i = new_share_fe.get().clone();
+        let key_linear_y = Point::<E>::generator() * new_share_fe.get().clone();
         let keys_linear = SharedKeys {
             x_i: key_linear_x_i,
             y: key_linear_y,
         };
-        let mut pk_vec: Vec<_> = (0..n as usize)
-            .map(|i| refresh_messages[0].points_committed_vec[i].clone() * li_vec[0].clone())
-            .collect();
-
-        for i in 0..n as usize {
-            for j in 1..(t + 1) as usize {
-                pk_vec[i] = pk_vec[i].clone()
-                    + refresh_messages[j].points_committed_vec[i].clone() * li_vec[j].clone();
+        // `points_committed_vec` holds Feldman coefficient commitments (degree new_t), not
+        // per-party point commitments, so recombine each destination party's own point
+        // commitment (`get_point_commitment`) across the contributing old-committee messages,
+        // Lagrange-weighted the same way `get_ciphertext_sum` weights their encrypted sub-shares.
+        let new_parameters = ShamirSecretSharing {
+            threshold: new_t,
+            share_count: new_n,
+        };
+        let mut pk_vec: Vec<Point<E>> = vec![Point::<E>::zero(); new_n as usize];
+        for (j, li) in li_vec.iter().enumerate() {
+            let commitment_scheme = VerifiableSS::<E> {
+                parameters: new_parameters.clone(),
+                commitments: refresh_messages[j].points_committed_vec.clone(),
+            };
+            for (i, pk) in pk_vec.iter_mut().enumerate() {
+                *pk = pk.clone() + commitment_scheme.get_point_commitment((i + 1) as u16) * li.clone();
             }
         }
 
@@ -218,7 +322,7 @@ impl JoinMessage {
             .collect();
 
         // generate the paillier public key vec needed for the LocalKey generation.
-        let paillier_key_vec: Vec<EncryptionKey> = (1..n + 1)
+        let paillier_key_vec: Vec<EncryptionKey> = (1..new_n + 1)
             .map(|party| {
                 let ek = available_parties.get(&party);
                 match ek {
@@ -231,28 +335,23 @@ impl JoinMessage {
             })
             .collect();
         // generate the DLogStatement vec needed for the LocalKey generation.
-        let h1_h2_ntilde_vec: Vec<DLogStatement> = (1..n + 1)
+        let h1_h2_ntilde_vec: Vec<DLogStatement> = (1..new_n + 1)
             .map(|party| {
                 let statement = available_h1_h2_ntilde_vec.get(&party);
 
                 match statement {
-                    None => generate_dlog_statement_proofs().0,
+                    None => generate_dlog_statement_proofs::<E, H>().0,
                     Some(dlog_statement) => (*dlog_statement).clone(),
                 }
             })
             .collect();
 
-        // check if all the existing parties submitted the same public key. If they differ, abort.
-        // TODO: this should be verifiable?
-        for refresh_message in refresh_messages.iter() {
-            if refresh_message.public_key != refresh_messages[0].public_key {
-                return Err(FsDkrError::BroadcastedPublicKeyError);
-            }
-        }
+        // Agreement on the broadcasted public key (and the per-message proofs) was already
+        // checked, with the offending party_indices attached to the error, in
+        // `RefreshMessage::validate_collect` above.
 
-        // generate the vss_scheme for the LocalKey
-        let (vss_scheme, _) = VerifiableSS::<E>::share(t, n, &new_share_fe);
-        // TODO: secret cleanup might be needed.
+        // generate the vss_scheme for the LocalKey, at the new (possibly changed) threshold.
+        let (vss_scheme, _) = VerifiableSS::<E>::share(new_t, new_n, new_share_fe.get());
 
         let local_key = LocalKey {
             paillier_dk,
@@ -263,10 +362,174 @@ impl JoinMessage {
             h1_h2_n_tilde_vec: h1_h2_ntilde_vec,
             vss_scheme,
             i: party_index,
-            t: t,
-            n: n,
+            t: new_t,
+            n: new_n,
         };
 
         Ok(local_key)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::refresh_message::RefreshMessage;
+    use crate::sync_key_gen;
+    use curv::elliptic::curves::Secp256k1;
+    use paillier::RawCiphertext;
+    use sha2::Sha256;
+
+    type E = Secp256k1;
+    type H = Sha256;
+
+    /// Bootstraps `n` [LocalKey]s for a `t`-of-`n` group via the dealerless keygen, so join
+    /// tests have an existing committee to reshare from.
+    fn bootstrap_local_keys(t: u16, n: u16) -> Vec<LocalKey<E>> {
+        let mut round1_messages = Vec::new();
+        let mut round1_keys = Vec::new();
+        for party_index in 1..=n {
+            let (join_message, keys) = sync_key_gen::round1::<E, H>(party_index);
+            round1_messages.push(join_message);
+            round1_keys.push(keys);
+        }
+
+        let paillier_key_vec: Vec<EncryptionKey> = round1_keys.iter().map(|k| k.ek.clone()).collect();
+        let h1_h2_n_tilde_vec: Vec<DLogStatement> = round1_messages
+            .iter()
+            .map(|m| m.dlog_statement.clone())
+            .collect();
+
+        let round2_messages: Vec<_> = (1..=n)
+            .map(|party_index| sync_key_gen::round2::<E>(party_index, t, &paillier_key_vec))
+            .collect();
+
+        (1..=n)
+            .map(|party_index| {
+                sync_key_gen::collect::<E, H>(
+                    party_index,
+                    t,
+                    n,
+                    &round1_keys[(party_index - 1) as usize].dk,
+                    paillier_key_vec.clone(),
+                    h1_h2_n_tilde_vec.clone(),
+                    &round1_messages,
+                    &round2_messages,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn joining_party_collects_a_local_key_whose_pk_vec_matches_every_share() {
+        let (old_t, old_n) = (1, 3);
+        let old_local_keys = bootstrap_local_keys(old_t, old_n);
+
+        // One new party joins at index 4. `new_n=4 > new_t+1=2` so the share count doesn't
+        // coincidentally equal the threshold.
+        let new_t = old_t;
+        let new_n = old_n + 1;
+
+        let (mut join_message, join_keys) = JoinMessage::<E, H>::distribute();
+        join_message.set_party_index(new_n);
+
+        let existing_paillier_keys: Vec<Keys> = (0..old_n).map(|_| Keys::create(0)).collect();
+        let mut destination_eks: Vec<EncryptionKey> = existing_paillier_keys
+            .iter()
+            .map(|k| k.ek.clone())
+            .collect();
+        destination_eks.push(join_keys.ek.clone());
+
+        let refresh_messages: Vec<_> = old_local_keys
+            .iter()
+            .map(|local_key| RefreshMessage::distribute(local_key, &destination_eks, new_t).0)
+            .collect();
+        RefreshMessage::validate_collect(&refresh_messages, old_t, old_n).unwrap();
+
+        let new_local_key = join_message
+            .collect(&refresh_messages, join_keys, &[], old_t, old_n, new_t, new_n)
+            .unwrap();
+
+        assert_eq!(new_local_key.i, new_n);
+        assert_eq!(new_local_key.pk_vec.len(), new_n as usize);
+        assert_eq!(
+            Point::<E>::generator() * new_local_key.keys_linear.x_i.clone(),
+            new_local_key.pk_vec[(new_n - 1) as usize],
+            "the joiner's own share must match its recombined point commitment"
+        );
+
+        // Independently decrypt party 1's new share and check it against the pk_vec entry the
+        // joiner computed, confirming the recombination agrees across destination parties, not
+        // just for the one party that happened to compute it.
+        let old_parameters = ShamirSecretSharing {
+            threshold: old_t,
+            share_count: old_n,
+        };
+        let (cipher_text_sum, _) = RefreshMessage::get_ciphertext_sum(
+            &refresh_messages,
+            1,
+            &old_parameters,
+            &existing_paillier_keys[0].ek,
+        );
+        let party_1_share = Paillier::decrypt(
+            &existing_paillier_keys[0].dk,
+            RawCiphertext::from(cipher_text_sum),
+        )
+        .0
+        .into_owned();
+        assert_eq!(
+            Point::<E>::generator() * Scalar::<E>::from(&party_1_share),
+            new_local_key.pk_vec[0],
+            "party 1's share must match its recombined point commitment"
+        );
+    }
+
+    #[test]
+    fn joiner_with_mismatched_dlog_statement_is_rejected() {
+        let (old_t, old_n) = (1, 3);
+        let old_local_keys = bootstrap_local_keys(old_t, old_n);
+
+        let new_t = old_t;
+        let new_n = old_n + 2;
+
+        let (mut join_message_a, join_keys_a) = JoinMessage::<E, H>::distribute();
+        join_message_a.set_party_index(new_n - 1);
+        let (mut join_message_b, join_keys_b) = JoinMessage::<E, H>::distribute();
+        join_message_b.set_party_index(new_n);
+
+        // Swap in a mismatched dlog_statement for party B: its composite dlog proofs were
+        // produced for its own statement, so they no longer verify against this one.
+        join_message_b.dlog_statement = join_message_a.dlog_statement.clone();
+
+        let existing_paillier_keys: Vec<Keys> = (0..old_n).map(|_| Keys::create(0)).collect();
+        let mut destination_eks: Vec<EncryptionKey> = existing_paillier_keys
+            .iter()
+            .map(|k| k.ek.clone())
+            .collect();
+        destination_eks.push(join_keys_a.ek.clone());
+        destination_eks.push(join_keys_b.ek.clone());
+
+        let refresh_messages: Vec<_> = old_local_keys
+            .iter()
+            .map(|local_key| RefreshMessage::distribute(local_key, &destination_eks, new_t).0)
+            .collect();
+
+        let err = join_message_a
+            .collect(
+                &refresh_messages,
+                join_keys_a,
+                &[join_message_b],
+                old_t,
+                old_n,
+                new_t,
+                new_n,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::DlogProofValidationError {
+                party_index: new_n
+            }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Error types returned by the fs-dkr refresh/join protocols.
+
+use std::fmt;
+
+/// Convenience alias used throughout the crate.
+pub type FsDkrResult<T> = Result<T, FsDkrError>;
+
+/// Errors that can occur while generating or collecting refresh/join messages.
+///
+/// Following the CGGMP'21 "identifiable aborts" model, validation errors carry the
+/// `party_index` (or indices) of whoever caused the failure, so callers can exclude the
+/// culprit(s) and re-run the round instead of restarting blindly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsDkrError {
+    /// A new party tried to be used before a `party_index` was assigned to it.
+    NewPartyUnassignedIndexError,
+    /// The parties listed broadcast a public key that disagrees with the majority.
+    BroadcastedPublicKeyError { party_indices: Vec<u16> },
+    /// More than one party was assigned the same `party_index` during the same round. Carries
+    /// every `party_index` value that was claimed more than once.
+    PartyIndexCollisionError { party_indices: Vec<u16> },
+    /// The Paillier correctness proof submitted by `party_index` failed to verify.
+    PaillierKeyError { party_index: u16 },
+    /// The composite discrete-log proof submitted by `party_index` failed to verify.
+    DlogProofValidationError { party_index: u16 },
+    /// The ring-Pedersen (Π^prm) proof submitted by `party_index` failed to verify.
+    RingPedersenProofValidationError { party_index: u16 },
+    /// Not enough messages were collected: refresh messages to reconstruct the previous
+    /// secret, or round-2 messages of a dealerless key generation ([crate::sync_key_gen]).
+    InsufficientMessagesError { received: u16, required: u16 },
+    /// The Feldman-VSS sub-share contributed by `party_index` during a dealerless key
+    /// generation was missing or did not match that party's broadcast commitment.
+    InvalidShareError { party_index: u16 },
+    /// `party_index` was evicted by a [crate::remove_party_message::RemoveMessage] and tried to
+    /// collect that same [crate::remove_party_message::RemoveMessage] on its own behalf.
+    PartyRemovedError { party_index: u16 },
+    /// The parties listed disagree on the `(t, n)` the group is resharing *to*.
+    ThresholdMismatchError { party_indices: Vec<u16> },
+    /// Lagrange-recombining the broadcast VSS commitments' constant terms does not reconstruct
+    /// the public key the refresh messages claim to agree on.
+    PublicKeyReconstructionError { party_indices: Vec<u16> },
+}
+
+impl fmt::Display for FsDkrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FsDkrError::NewPartyUnassignedIndexError => {
+                write!(f, "new party was used before a party_index was assigned to it")
+            }
+            FsDkrError::BroadcastedPublicKeyError { party_indices } => write!(
+                f,
+                "parties {:?} broadcast a public key that disagrees with the majority",
+                party_indices
+            ),
+            FsDkrError::PartyIndexCollisionError { party_indices } => write!(
+                f,
+                "party_index values {:?} were each claimed by more than one party",
+                party_indices
+            ),
+            FsDkrError::PaillierKeyError { party_index } => write!(
+                f,
+                "Paillier correctness proof submitted by party {} failed to verify",
+                party_index
+            ),
+            FsDkrError::DlogProofValidationError { party_index } => write!(
+                f,
+                "composite dlog proof submitted by party {} failed to verify",
+                party_index
+            ),
+            FsDkrError::RingPedersenProofValidationError { party_index } => write!(
+                f,
+                "ring-Pedersen proof submitted by party {} failed to verify",
+                party_index
+            ),
+            FsDkrError::InsufficientMessagesError { received, required } => write!(
+                f,
+                "collected {} messages, but {} are required",
+                received, required
+            ),
+            FsDkrError::InvalidShareError { party_index } => write!(
+                f,
+                "sub-share contributed by party {} is missing or does not match its broadcast commitment",
+                party_index
+            ),
+            FsDkrError::PartyRemovedError { party_index } => write!(
+                f,
+                "party {} was evicted and cannot collect the removal on its own behalf",
+                party_index
+            ),
+            FsDkrError::ThresholdMismatchError { party_indices } => write!(
+                f,
+                "parties {:?} disagree on the (t, n) the group is resharing to",
+                party_indices
+            ),
+            FsDkrError::PublicKeyReconstructionError { party_indices } => write!(
+                f,
+                "recombining the VSS commitments broadcast by parties {:?} does not reconstruct the claimed public key",
+                party_indices
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FsDkrError {}
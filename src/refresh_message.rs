@@ -0,0 +1,494 @@
+//! Message definitions for the proactive refresh of an existing [LocalKey].
+//! Key points about a refresh round:
+//! * Every existing party samples a fresh degree-`new_t` polynomial that reshares its own secret
+//! share, broadcasting a Paillier ciphertext of each destination party's new sub-share together
+//! with the usual correctness/dlog/ring-Pedersen proofs for its Paillier key and Pedersen
+//! parameters.
+//! * Every party (existing and joining, see [crate::add_party_message]) collects the refresh
+//! messages, checks that they agree on the public key and that every proof verifies, then
+//! recombines its own new sub-shares into a refreshed [LocalKey].
+//! * `new_t`/`new_n` need not match the committee this round reshares *from*: broadcasting a
+//! degree-`new_t` polynomial to `new_n` destination parties (see [RefreshMessage::distribute]) moves the
+//! group to a new `(t, n)` in the same proactive round, without rotating the public key.
+//! * Validation follows the CGGMP'21 "identifiable aborts" model: failures carry the offending
+//! `party_index` (or indices) so a caller can exclude the culprit and re-run the round.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::ring_pedersen_proof::{RingPedersenProof, RingPedersenStatement};
+use curv::arithmetic::{BasicOps, Modulo, One, Samplable, Zero};
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::{Curve, Point, Scalar};
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{Add, Encrypt, EncryptionKey, Mul, Paillier, RawCiphertext, RawPlaintext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement, NiCorrectKeyProof};
+
+/// Message broadcast by an existing party at the start of a refresh round.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RefreshMessage<E: Curve, H: Digest + Clone> {
+    pub(crate) party_index: u16,
+    pub(crate) t: u16,
+    pub(crate) n: u16,
+    pub(crate) public_key: Point<E>,
+    pub(crate) ek: EncryptionKey,
+    pub(crate) dk_correctness_proof: NiCorrectKeyProof,
+    pub(crate) dlog_statement: DLogStatement,
+    pub(crate) composite_dlog_proof_base_h1: CompositeDLogProof,
+    pub(crate) composite_dlog_proof_base_h2: CompositeDLogProof,
+    pub(crate) ring_pedersen_statement: RingPedersenStatement<E, H>,
+    pub(crate) ring_pedersen_proof: RingPedersenProof<E, H>,
+    /// Feldman-VSS commitments to the fresh degree-`t` polynomial this party reshared its
+    /// existing secret share with, one entry per coefficient (`t + 1` in all).
+    pub(crate) points_committed_vec: Vec<Point<E>>,
+    /// The sub-share for destination party `j + 1` (the polynomial evaluated at `j + 1`),
+    /// encrypted under that party's Paillier `ek`, so that party alone can decrypt the sum of
+    /// everyone's contribution to its new share.
+    pub(crate) sub_share_ciphertexts: Vec<BigInt>,
+    phantom: PhantomData<H>,
+}
+
+impl<E: Curve, H: Digest + Clone> RefreshMessage<E, H> {
+    /// Produces the refresh message for `local_key`, generating a fresh Paillier key pair
+    /// (returned alongside the message) and sampling a fresh degree-`new_t` Feldman VSS polynomial that reshares `local_key`'s secret share to
+    /// `destination_eks` (one entry per destination party index, 1-indexed).
+    ///
+    /// `destination_eks` need not have the same length as `local_key.n`: passing more (fewer)
+    /// entries than the current party count moves the group to a larger (smaller) quorum in the
+    /// same proactive round. A plain same-quorum refresh is `distribute(local_key,
+    /// &local_key.paillier_key_vec, local_key.t)`.
+    pub fn distribute(
+        local_key: &LocalKey<E>,
+        destination_eks: &[EncryptionKey],
+        new_t: u16,
+    ) -> (Self, Keys) {
+        let new_n = destination_eks.len() as u16;
+        let paillier_key_pair = Keys::create(0);
+        let (
+            dlog_statement,
+            composite_dlog_proof_base_h1,
+            composite_dlog_proof_base_h2,
+            ring_pedersen_statement,
+            ring_pedersen_proof,
+        ) = crate::add_party_message::generate_dlog_statement_proofs::<E, H>();
+
+        let secret = local_key.keys_linear.x_i.clone();
+        let (vss_scheme, secret_shares) = VerifiableSS::<E>::share(new_t, new_n, &secret);
+
+        let sub_share_ciphertexts: Vec<BigInt> = destination_eks
+            .iter()
+            .enumerate()
+            .map(|(i, dest_ek)| {
+                Paillier::encrypt(dest_ek, RawPlaintext::from(secret_shares[i].to_bigint()))
+                    .0
+                    .into_owned()
+            })
+            .collect();
+
+        let refresh_message = RefreshMessage {
+            party_index: local_key.i,
+            t: new_t,
+            n: new_n,
+            public_key: local_key.y_sum_s.clone(),
+            ek: paillier_key_pair.ek.clone(),
+            dk_correctness_proof: NiCorrectKeyProof::proof(&paillier_key_pair.dk, None),
+            dlog_statement,
+            composite_dlog_proof_base_h1,
+            composite_dlog_proof_base_h2,
+            ring_pedersen_statement,
+            ring_pedersen_proof,
+            points_committed_vec: vss_scheme.commitments,
+            sub_share_ciphertexts,
+            phantom: PhantomData,
+        };
+
+        (refresh_message, paillier_key_pair)
+    }
+
+    /// Validates every broadcast refresh message before it is safe to recombine them, following
+    /// the CGGMP'21 identifiable-aborts model: on any failure, the returned [FsDkrError] names
+    /// the `party_index` (or indices) responsible rather than aborting the whole round blindly.
+    ///
+    /// `old_t`/`old_n` are the quorum parameters the group is resharing *from*; each message's
+    /// own `t`/`n` fields carry the (possibly different) quorum it is resharing *to*, and must
+    /// agree across every message for the round to be a well-defined threshold transition.
+    pub fn validate_collect(
+        refresh_messages: &[RefreshMessage<E, H>],
+        old_t: u16,
+        old_n: u16,
+    ) -> FsDkrResult<()> {
+        // Report every index whose broadcast public key disagrees with the majority, rather
+        // than assuming index 0 is correct.
+        let mut votes: HashMap<&Point<E>, Vec<u16>> = HashMap::new();
+        for message in refresh_messages.iter() {
+            votes
+                .entry(&message.public_key)
+                .or_default()
+                .push(message.party_index);
+        }
+        if votes.len() > 1 {
+            let majority_key = votes
+                .iter()
+                .max_by_key(|(_, indices)| indices.len())
+                .map(|(key, _)| *key)
+                .expect("refresh_messages is non-empty");
+            let mut culprits: Vec<u16> = votes
+                .iter()
+                .filter(|(key, _)| **key != majority_key)
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect();
+            culprits.sort_unstable();
+            return Err(FsDkrError::BroadcastedPublicKeyError {
+                party_indices: culprits,
+            });
+        }
+
+        // Two different parties claiming the same party_index would silently overwrite each
+        // other's contribution in `get_ciphertext_sum`; catch it explicitly instead.
+        let mut seen: HashMap<u16, u16> = HashMap::new();
+        let mut colliding_indices = Vec::new();
+        for message in refresh_messages.iter() {
+            *seen.entry(message.party_index).or_insert(0) += 1;
+        }
+        for (party_index, count) in seen.iter() {
+            if *count > 1 {
+                colliding_indices.push(*party_index);
+            }
+        }
+        if !colliding_indices.is_empty() {
+            colliding_indices.sort_unstable();
+            return Err(FsDkrError::PartyIndexCollisionError {
+                party_indices: colliding_indices,
+            });
+        }
+
+        for message in refresh_messages.iter() {
+            if NiCorrectKeyProof::verify(&message.dk_correctness_proof, &message.ek, None).is_err()
+            {
+                return Err(FsDkrError::PaillierKeyError {
+                    party_index: message.party_index,
+                });
+            }
+
+            // The base-h2 proof was produced for the swapped statement (g = h2, ni = h1).
+            let dlog_statement_base_h2 = DLogStatement {
+                N: message.dlog_statement.N.clone(),
+                g: message.dlog_statement.ni.clone(),
+                ni: message.dlog_statement.g.clone(),
+            };
+            if CompositeDLogProof::verify(
+                &message.composite_dlog_proof_base_h1,
+                &message.dlog_statement,
+            )
+            .is_err()
+                || CompositeDLogProof::verify(
+                    &message.composite_dlog_proof_base_h2,
+                    &dlog_statement_base_h2,
+                )
+                .is_err()
+            {
+                return Err(FsDkrError::DlogProofValidationError {
+                    party_index: message.party_index,
+                });
+            }
+
+            RingPedersenProof::verify(
+                &message.ring_pedersen_proof,
+                &message.ring_pedersen_statement,
+                message.party_index,
+            )?;
+        }
+
+        // Every message must agree on the (t, n) it is resharing *to*, otherwise parties would
+        // reconstruct the new VSS scheme at different degrees.
+        let mut new_params_votes: HashMap<(u16, u16), Vec<u16>> = HashMap::new();
+        for message in refresh_messages.iter() {
+            new_params_votes
+                .entry((message.t, message.n))
+                .or_default()
+                .push(message.party_index);
+        }
+        if new_params_votes.len() > 1 {
+            let majority_params = new_params_votes
+                .iter()
+                .max_by_key(|(_, indices)| indices.len())
+                .map(|(params, _)| *params)
+                .expect("refresh_messages is non-empty");
+            let mut culprits: Vec<u16> = new_params_votes
+                .iter()
+                .filter(|(params, _)| **params != majority_params)
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect();
+            culprits.sort_unstable();
+            return Err(FsDkrError::ThresholdMismatchError {
+                party_indices: culprits,
+            });
+        }
+
+        // `old_t + 1` messages from the old committee are needed to reconstruct the old secret
+        // via Lagrange interpolation in `get_ciphertext_sum`.
+        if (refresh_messages.len() as u16) <= old_t {
+            return Err(FsDkrError::InsufficientMessagesError {
+                received: refresh_messages.len() as u16,
+                required: old_t + 1,
+            });
+        }
+
+        // A party could broadcast a truthful `public_key` field while its
+        // `points_committed_vec` reshares an unrelated polynomial, so recompute the public key
+        // from the Lagrange-recombined constant terms of the VSS commitments and check it
+        // against what every message claims, rather than trusting the self-reported field.
+        let old_parameters = ShamirSecretSharing {
+            threshold: old_t,
+            share_count: old_n,
+        };
+        let (contributing, li_vec) =
+            Self::old_committee_lagrange_coefficients(refresh_messages, &old_parameters);
+        let reconstructed_public_key = refresh_messages
+            .iter()
+            .take(old_t as usize + 1)
+            .zip(li_vec.iter())
+            .fold(Point::<E>::zero(), |acc, (message, li)| {
+                acc + message.points_committed_vec[0].clone() * li.clone()
+            });
+        if reconstructed_public_key != refresh_messages[0].public_key {
+            let mut culprits = contributing;
+            culprits.sort_unstable();
+            return Err(FsDkrError::PublicKeyReconstructionError {
+                party_indices: culprits,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The `party_index` of each of the first `parameters.threshold + 1` messages, together with
+    /// the Lagrange coefficient each one uses to reconstruct a value at `x = 0` (the constant
+    /// term of the shared polynomial) from that set of contributors.
+    fn old_committee_lagrange_coefficients(
+        refresh_messages: &[RefreshMessage<E, H>],
+        parameters: &ShamirSecretSharing,
+    ) -> (Vec<u16>, Vec<Scalar<E>>) {
+        let contributing: Vec<u16> = refresh_messages[0..=(parameters.threshold as usize)]
+            .iter()
+            .map(|message| message.party_index)
+            .collect();
+
+        let li_vec: Vec<Scalar<E>> = contributing
+            .iter()
+            .map(|&index| {
+                VerifiableSS::<E>::map_share_to_new_params(
+                    parameters,
+                    index - 1,
+                    &contributing.iter().map(|&i| i - 1).collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        (contributing, li_vec)
+    }
+
+    /// Computes, for `party_index`, the homomorphic sum (under `ek`) of every refresh message's
+    /// encrypted contribution to that party's new share, along with the Lagrange coefficients
+    /// used to recombine the matching public commitments. See
+    /// <https://hackmd.io/@omershlo/Hy1jBo6JY> for the full derivation.
+    pub(crate) fn get_ciphertext_sum(
+        refresh_messages: &[RefreshMessage<E, H>],
+        party_index: u16,
+        parameters: &ShamirSecretSharing,
+        ek: &EncryptionKey,
+    ) -> (BigInt, Vec<Scalar<E>>) {
+        let (_, li_vec) = Self::old_committee_lagrange_coefficients(refresh_messages, parameters);
+
+        let zero_ciphertext = Paillier::encrypt(ek, RawPlaintext::from(BigInt::zero()))
+            .0
+            .into_owned();
+
+        let cipher_text_sum = refresh_messages
+            .iter()
+            .take(parameters.threshold as usize + 1)
+            .zip(li_vec.iter())
+            .fold(zero_ciphertext, |acc, (message, li)| {
+                let share_ciphertext = message.sub_share_ciphertexts[(party_index - 1) as usize].clone();
+                let scaled = Paillier::mul(
+                    ek,
+                    RawCiphertext::from(share_ciphertext),
+                    RawPlaintext::from(li.to_bigint()),
+                );
+                Paillier::add(ek, RawCiphertext::from(acc), scaled)
+                    .0
+                    .into_owned()
+            });
+
+        (cipher_text_sum, li_vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync_key_gen;
+    use curv::elliptic::curves::Secp256k1;
+    use paillier::Decrypt;
+    use sha2::Sha256;
+
+    type E = Secp256k1;
+    type H = Sha256;
+
+    /// Bootstraps `n` [LocalKey]s for a `t`-of-`n` group via the dealerless keygen, so refresh
+    /// tests have something real to reshare.
+    fn bootstrap_local_keys(t: u16, n: u16) -> Vec<LocalKey<E>> {
+        let mut round1_messages = Vec::new();
+        let mut round1_keys = Vec::new();
+        for party_index in 1..=n {
+            let (join_message, keys) = sync_key_gen::round1::<E, H>(party_index);
+            round1_messages.push(join_message);
+            round1_keys.push(keys);
+        }
+
+        let paillier_key_vec: Vec<EncryptionKey> = round1_keys.iter().map(|k| k.ek.clone()).collect();
+        let h1_h2_n_tilde_vec: Vec<DLogStatement> = round1_messages
+            .iter()
+            .map(|m| m.dlog_statement.clone())
+            .collect();
+
+        let round2_messages: Vec<_> = (1..=n)
+            .map(|party_index| sync_key_gen::round2::<E>(party_index, t, &paillier_key_vec))
+            .collect();
+
+        (1..=n)
+            .map(|party_index| {
+                sync_key_gen::collect::<E, H>(
+                    party_index,
+                    t,
+                    n,
+                    &round1_keys[(party_index - 1) as usize].dk,
+                    paillier_key_vec.clone(),
+                    h1_h2_n_tilde_vec.clone(),
+                    &round1_messages,
+                    &round2_messages,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn public_key_disagreement_is_attributed_to_culprit_party() {
+        let (t, n) = (1, 3);
+        let group_a = bootstrap_local_keys(t, n);
+        let group_b = bootstrap_local_keys(t, n);
+
+        let new_paillier_keys: Vec<Keys> = (0..n).map(|_| Keys::create(0)).collect();
+        let destination_eks: Vec<EncryptionKey> =
+            new_paillier_keys.iter().map(|k| k.ek.clone()).collect();
+
+        // Parties 1 and 2 reshare honestly; party 3's message comes from an unrelated group
+        // (different public key), so the disagreement must be attributed to index 3, not 1.
+        let (msg1, _) = RefreshMessage::distribute(&group_a[0], &destination_eks, t);
+        let (msg2, _) = RefreshMessage::distribute(&group_a[1], &destination_eks, t);
+        let (msg3, _) = RefreshMessage::distribute(&group_b[2], &destination_eks, t);
+
+        let refresh_messages = vec![msg1, msg2, msg3];
+        let err = RefreshMessage::validate_collect(&refresh_messages, t, n).unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::BroadcastedPublicKeyError {
+                party_indices: vec![3]
+            }
+        );
+    }
+
+    #[test]
+    fn colliding_party_index_is_attributed_to_the_shared_index() {
+        let (t, n) = (1, 3);
+        let group = bootstrap_local_keys(t, n);
+
+        let new_paillier_keys: Vec<Keys> = (0..n).map(|_| Keys::create(0)).collect();
+        let destination_eks: Vec<EncryptionKey> =
+            new_paillier_keys.iter().map(|k| k.ek.clone()).collect();
+
+        let (msg1, _) = RefreshMessage::distribute(&group[0], &destination_eks, t);
+        let (mut msg2, _) = RefreshMessage::distribute(&group[1], &destination_eks, t);
+        msg2.party_index = 1;
+
+        let refresh_messages = vec![msg1, msg2];
+        let err = RefreshMessage::validate_collect(&refresh_messages, t, n).unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::PartyIndexCollisionError {
+                party_indices: vec![1]
+            }
+        );
+    }
+
+    #[test]
+    fn threshold_change_shares_recombine_to_the_same_public_key() {
+        let (old_t, old_n) = (1, 3);
+        let local_keys = bootstrap_local_keys(old_t, old_n);
+
+        let (new_t, new_n) = (2, 4);
+        let new_paillier_keys: Vec<Keys> = (0..new_n).map(|_| Keys::create(0)).collect();
+        let destination_eks: Vec<EncryptionKey> =
+            new_paillier_keys.iter().map(|k| k.ek.clone()).collect();
+
+        let refresh_messages: Vec<_> = local_keys
+            .iter()
+            .map(|local_key| RefreshMessage::distribute(local_key, &destination_eks, new_t).0)
+            .collect();
+        RefreshMessage::validate_collect(&refresh_messages, old_t, old_n).unwrap();
+
+        let old_parameters = ShamirSecretSharing {
+            threshold: old_t,
+            share_count: old_n,
+        };
+
+        // Every destination party decrypts and sums its own new share directly (the collect
+        // path that assembles a full LocalKey lives in add_party_message/remove_party_message;
+        // this exercises the threshold-change math in RefreshMessage in isolation). `new_n > new_t
+        // + 1` here so the share count doesn't coincidentally equal the threshold.
+        let new_shares: Vec<Scalar<E>> = (1..=new_n)
+            .map(|destination_index| {
+                let paillier_key = &new_paillier_keys[(destination_index - 1) as usize];
+                let (cipher_text_sum, _) = RefreshMessage::get_ciphertext_sum(
+                    &refresh_messages,
+                    destination_index,
+                    &old_parameters,
+                    &paillier_key.ek,
+                );
+                let decrypted = Paillier::decrypt(&paillier_key.dk, RawCiphertext::from(cipher_text_sum))
+                    .0
+                    .into_owned();
+                Scalar::<E>::from(&decrypted)
+            })
+            .collect();
+
+        let new_parameters = ShamirSecretSharing {
+            threshold: new_t,
+            share_count: new_n,
+        };
+        let contributing: Vec<u16> = (0..=new_t).collect();
+        let reconstructed_secret = contributing
+            .iter()
+            .take(new_t as usize + 1)
+            .fold(Scalar::<E>::zero(), |acc, &index| {
+                let li = VerifiableSS::<E>::map_share_to_new_params(
+                    &new_parameters,
+                    index,
+                    &contributing,
+                );
+                acc + li * new_shares[index as usize].clone()
+            });
+
+        assert_eq!(
+            Point::<E>::generator() * reconstructed_secret,
+            local_keys[0].y_sum_s
+        );
+    }
+}
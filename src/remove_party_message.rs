@@ -0,0 +1,294 @@
+//! An explicit message for evicting a compromised or unresponsive party from the group.
+//!
+//! Unlike [crate::add_party_message::JoinMessage], which only ever adds or replaces parties,
+//! [RemoveMessage] lets the remaining parties agree, during the same proactive round as a
+//! [crate::refresh_message::RefreshMessage] broadcast, to drop one or more party indices
+//! entirely: the evicted indices are never inserted into the collecting party's
+//! `available_parties`/`available_h1_h2_ntilde_vec` maps, so their slot in the resulting
+//! [LocalKey] gets the same placeholders [crate::add_party_message] already uses for an index
+//! with no broadcast (a zeroed Paillier key and a freshly generated DLogStatement), and their
+//! old secret share can no longer decrypt anything destined for the refreshed group.
+
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::refresh_message::RefreshMessage;
+use crate::zeroize_support::Sensitive;
+use curv::arithmetic::Zero;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::{Curve, Point, Scalar};
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::{Keys, SharedKeys};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{Decrypt, EncryptionKey, Paillier, RawCiphertext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use zk_paillier::zkproofs::DLogStatement;
+
+/// Message agreeing to evict `removed_party_indices` from the group during the current round.
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct RemoveMessage<E: Curve, H: Digest + Clone> {
+    pub(crate) removed_party_indices: Vec<u16>,
+    phantom: PhantomData<(E, H)>,
+}
+
+impl<E: Curve, H: Digest + Clone> RemoveMessage<E, H> {
+    pub fn new(removed_party_indices: Vec<u16>) -> Self {
+        Self {
+            removed_party_indices,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn removed_party_indices(&self) -> &[u16] {
+        &self.removed_party_indices
+    }
+
+    /// Collect phase for a continuing party (i.e. not one of [Self::removed_party_indices]).
+    /// `refresh_messages` is expected to include the calling party's own broadcast, the same way
+    /// [RefreshMessage::validate_collect] treats every party symmetrically. `old_t`/`old_n` are
+    /// the quorum being reshared from, `new_t`/`new_n` the (typically smaller) quorum the
+    /// remaining parties reshare to.
+    #[allow(clippy::too_many_arguments)]
+    pub fn collect(
+        &self,
+        party_index: u16,
+        refresh_messages: &[RefreshMessage<E, H>],
+        paillier_key: Keys,
+        old_t: u16,
+        old_n: u16,
+        new_t: u16,
+        new_n: u16,
+    ) -> FsDkrResult<LocalKey<E>> {
+        if self.removed_party_indices.contains(&party_index) {
+            return Err(FsDkrError::PartyRemovedError { party_index });
+        }
+
+        // Evicted parties' refresh contributions are dropped before they ever reach
+        // `get_ciphertext_sum`/`available_parties` below.
+        let remaining_messages: Vec<RefreshMessage<E, H>> = refresh_messages
+            .iter()
+            .filter(|message| !self.removed_party_indices.contains(&message.party_index))
+            .cloned()
+            .collect();
+
+        RefreshMessage::validate_collect(&remaining_messages, old_t, old_n)?;
+
+        let old_parameters = ShamirSecretSharing {
+            threshold: old_t,
+            share_count: old_n,
+        };
+
+        let (cipher_text_sum, li_vec) = RefreshMessage::get_ciphertext_sum(
+            &remaining_messages,
+            party_index,
+            &old_parameters,
+            &paillier_key.ek,
+        );
+        let cipher_text_sum = Sensitive::new(cipher_text_sum, BigInt::zero);
+        let new_share = Sensitive::new(
+            Paillier::decrypt(&paillier_key.dk, RawCiphertext::from(cipher_text_sum.get().clone()))
+                .0
+                .into_owned(),
+            BigInt::zero,
+        );
+        let new_share_fe: Sensitive<Scalar<E>> =
+            Sensitive::new(Scalar::<E>::from(new_share.get()), Scalar::<E>::zero);
+        let paillier_dk = paillier_key.dk.clone();
+        let keys_linear = SharedKeys {
+            x_i: new_share_fe.get().clone(),
+            y: Point::<E>::generator() * new_share_fe.get().clone(),
+        };
+
+        // `points_committed_vec` holds Feldman coefficient commitments (degree new_t), not
+        // per-party point commitments, so recombine each destination party's own point
+        // commitment (`get_point_commitment`) across the contributing old-committee messages,
+        // Lagrange-weighted the same way `get_ciphertext_sum` weights their encrypted sub-shares.
+        let new_parameters = ShamirSecretSharing {
+            threshold: new_t,
+            share_count: new_n,
+        };
+        let mut pk_vec: Vec<Point<E>> = vec![Point::<E>::zero(); new_n as usize];
+        for (j, li) in li_vec.iter().enumerate() {
+            let commitment_scheme = VerifiableSS::<E> {
+                parameters: new_parameters.clone(),
+                commitments: remaining_messages[j].points_committed_vec.clone(),
+            };
+            for (i, pk) in pk_vec.iter_mut().enumerate() {
+                *pk = pk.clone() + commitment_scheme.get_point_commitment((i + 1) as u16) * li.clone();
+            }
+        }
+
+        // Only remaining parties' eks/DLogStatements are ever inserted, so a removed index's
+        // slot below falls through to the placeholder: a zeroed ek and a freshly generated
+        // DLogStatement.
+        let available_parties: HashMap<u16, &EncryptionKey> = remaining_messages
+            .iter()
+            .map(|message| (message.party_index, &message.ek))
+            .chain(std::iter::once((party_index, &paillier_key.ek)))
+            .collect();
+
+        let available_h1_h2_ntilde_vec: HashMap<u16, &DLogStatement> = remaining_messages
+            .iter()
+            .map(|message| (message.party_index, &message.dlog_statement))
+            .collect();
+
+        let paillier_key_vec: Vec<EncryptionKey> = (1..new_n + 1)
+            .map(|party| match available_parties.get(&party) {
+                None => EncryptionKey {
+                    n: BigInt::zero(),
+                    nn: BigInt::zero(),
+                },
+                Some(key) => (*key).clone(),
+            })
+            .collect();
+
+        let h1_h2_ntilde_vec: Vec<DLogStatement> = (1..new_n + 1)
+            .map(
+                |party| match available_h1_h2_ntilde_vec.get(&party) {
+                    None => crate::add_party_message::generate_dlog_statement_proofs::<E, H>().0,
+                    Some(statement) => (*statement).clone(),
+                },
+            )
+            .collect();
+
+        let (vss_scheme, _) = VerifiableSS::<E>::share(new_t, new_n, new_share_fe.get());
+
+        Ok(LocalKey {
+            paillier_dk,
+            pk_vec,
+            keys_linear,
+            paillier_key_vec,
+            y_sum_s: remaining_messages[0].public_key.clone(),
+            h1_h2_n_tilde_vec: h1_h2_ntilde_vec,
+            vss_scheme,
+            i: party_index,
+            t: new_t,
+            n: new_n,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoveMessage;
+    use crate::error::FsDkrError;
+    use crate::refresh_message::RefreshMessage;
+    use crate::sync_key_gen;
+    use curv::arithmetic::Zero;
+    use curv::elliptic::curves::{Point, Secp256k1};
+    use curv::BigInt;
+    use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::Keys;
+    use paillier::EncryptionKey;
+    use sha2::Sha256;
+
+    type E = Secp256k1;
+    type H = Sha256;
+
+    #[test]
+    fn removed_party_cannot_collect_for_itself() {
+        let remove_message = RemoveMessage::<E, H>::new(vec![3]);
+        assert_eq!(remove_message.removed_party_indices(), &[3]);
+
+        let err = remove_message
+            .collect(3, &[], Keys::create(0), 1, 3, 1, 3)
+            .unwrap_err();
+        assert_eq!(err, FsDkrError::PartyRemovedError { party_index: 3 });
+    }
+
+    /// Runs the dealerless DKG for a 3-party, t=1 group, then evicts party 3 during a refresh
+    /// to which only parties 1 and 2 contribute, and checks that party 3's slot in the resulting
+    /// [LocalKey]'s Paillier key vector is the same zeroed placeholder an absent party gets
+    /// elsewhere in this crate: nothing broadcast by the evicted party is carried into the
+    /// refreshed group, so its old share can no longer decrypt anything meant for it.
+    #[test]
+    fn removed_party_slot_is_absent_from_refreshed_key() {
+        let (t, n) = (1, 3);
+
+        let mut round1_messages = Vec::new();
+        let mut round1_keys = Vec::new();
+        for party_index in 1..=n {
+            let (join_message, keys) = sync_key_gen::round1::<E, H>(party_index);
+            round1_messages.push(join_message);
+            round1_keys.push(keys);
+        }
+
+        let paillier_key_vec: Vec<EncryptionKey> = round1_keys.iter().map(|k| k.ek.clone()).collect();
+        let h1_h2_n_tilde_vec = round1_messages
+            .iter()
+            .map(|m| m.dlog_statement.clone())
+            .collect::<Vec<_>>();
+
+        let round2_messages: Vec<_> = (1..=n)
+            .map(|party_index| sync_key_gen::round2::<E>(party_index, t, &paillier_key_vec))
+            .collect();
+
+        let local_keys: Vec<_> = (1..=n)
+            .map(|party_index| {
+                sync_key_gen::collect::<E, H>(
+                    party_index,
+                    t,
+                    n,
+                    &round1_keys[(party_index - 1) as usize].dk,
+                    paillier_key_vec.clone(),
+                    h1_h2_n_tilde_vec.clone(),
+                    &round1_messages,
+                    &round2_messages,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        // Parties 1 and 2 reshare to a new 3-party group, evicting party 3. `new_n=3 > new_t+1=2`
+        // so the share count doesn't coincidentally equal the threshold.
+        let new_n = 3;
+        let new_paillier_keys: Vec<Keys> = (1..=new_n).map(|_| Keys::create(0)).collect();
+        let destination_eks: Vec<EncryptionKey> =
+            new_paillier_keys.iter().map(|k| k.ek.clone()).collect();
+
+        let (refresh_1, _) = RefreshMessage::distribute(&local_keys[0], &destination_eks, t);
+        let (refresh_2, _) = RefreshMessage::distribute(&local_keys[1], &destination_eks, t);
+        let refresh_messages = vec![refresh_1, refresh_2];
+
+        let remove_message = RemoveMessage::<E, H>::new(vec![3]);
+
+        // Party 1 decrypts with the destination Paillier key it was actually given an `ek`
+        // slot for above, not the fresh one `RefreshMessage::distribute` handed back (that one
+        // is party 1's *own* key for some future round, unrelated to this one's ciphertexts).
+        let new_local_key_1 = remove_message
+            .collect(
+                1,
+                &refresh_messages,
+                new_paillier_keys[0].clone(),
+                t,
+                n,
+                t,
+                new_n,
+            )
+            .unwrap();
+
+        assert_eq!(new_local_key_1.paillier_key_vec.len(), new_n as usize);
+        assert_eq!(
+            new_local_key_1.paillier_key_vec[2].n,
+            BigInt::zero(),
+            "the evicted party's Paillier slot must be the zeroed placeholder"
+        );
+        assert_eq!(
+            new_local_key_1.y_sum_s, local_keys[0].y_sum_s,
+            "removal must not change the group public key"
+        );
+        assert_eq!(
+            Point::<E>::generator() * new_local_key_1.keys_linear.x_i.clone(),
+            new_local_key_1.pk_vec[0],
+            "party 1's own share must match its recombined point commitment"
+        );
+
+        // Party 3, having been evicted, is refused if it tries to collect for itself.
+        let err = remove_message
+            .collect(3, &refresh_messages, new_paillier_keys[1].clone(), t, n, t, new_n)
+            .unwrap_err();
+        assert_eq!(err, FsDkrError::PartyRemovedError { party_index: 3 });
+    }
+}
@@ -7,9 +7,7 @@
     version 3 of the License, or (at your option) any later version.
     @license GPL-3.0+ <https://github.com/KZen-networks/zk-paillier/blob/master/LICENSE>
 */
-use std::iter;
 use std::marker::PhantomData;
-use std::ops::Shl;
 
 use curv::elliptic::curves::Curve;
 use serde::{Deserialize, Serialize};
@@ -21,14 +19,14 @@ use paillier::{DecryptionKey, EncryptionKey, Paillier, KeyGeneration};
 use zk_paillier::zkproofs::IncorrectProof;
 use bitvec::prelude::*;
 
-use crate::error::FsDkrResult;
+use crate::error::{FsDkrError, FsDkrResult};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RingPedersenStatement<E: Curve, H: Digest + Clone> {
-    S: BigInt,
-    T: BigInt,
-    N: BigInt,
-    phi: BigInt,
+    pub(crate) S: BigInt,
+    pub(crate) T: BigInt,
+    pub(crate) N: BigInt,
+    pub(crate) phi: BigInt,
     phantom: PhantomData<(E, H)>,
 }
 
@@ -54,50 +52,72 @@ impl<E: Curve, H: Digest + Clone> RingPedersenStatement<E, H> {
                 S: s,
                 T: t,
                 N: ek_tilde.n,
-                phi: phi,
+                phi,
                 phantom: PhantomData,
             },
             RingPedersenWitness {
                 p: dk_tilde.p,
                 q: dk_tilde.q,
                 lambda,
-                phantom: PhantomData
+                phantom: PhantomData,
             },
         )
     }
+
+    /// Builds the statement directly from already-generated Pedersen parameters, e.g. the ones
+    /// produced by [crate::add_party_message::generate_h1_h2_n_tilde].
+    pub(crate) fn from_parts(n_tilde: BigInt, h1: BigInt, h2: BigInt, phi: BigInt) -> Self {
+        Self {
+            S: h2,
+            T: h1,
+            N: n_tilde,
+            phi,
+            phantom: PhantomData,
+        }
+    }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RingPedersenProof<E: Curve, H: Digest + Clone> {
-    A: [BigInt; crate::M_SECURITY],
-    Z: [BigInt; crate::M_SECURITY],
+    A: Vec<BigInt>,
+    Z: Vec<BigInt>,
     bitwise_e: BitVec,
     phantom: PhantomData<(E, H)>,
 }
 
-// Link to the UC non-interactive threshold ECDSA paper
+// Non-interactive Π^prm proof from the UC non-interactive threshold ECDSA paper (CGGMP'21),
+// proving knowledge of lambda with S = T^lambda mod N. Whether T is a square mod N is not
+// checked by [RingPedersenProof::verify].
 impl<E: Curve, H: Digest + Clone> RingPedersenProof<E, H> {
     pub fn prove(witness: &RingPedersenWitness<E, H>, statement: &RingPedersenStatement<E, H>) -> RingPedersenProof<E, H> {
-        // 1. Sample alphas from 1 -> m from \phi(N)
-        let a = [(); crate::M_SECURITY].map(|_| BigInt::zero());
-        let A = [(); crate::M_SECURITY].map(|_| BigInt::zero());
-        let hash = H::new();
-        for i in 0..crate::M_SECURITY {
+        Self::prove_with_exponent(&witness.lambda, statement)
+    }
+
+    /// Same as [Self::prove], but takes the discrete-log exponent directly instead of a full
+    /// [RingPedersenWitness]. Lets callers that only hold `lambda` (e.g. [crate::add_party_message])
+    /// produce a proof without reconstructing `p`/`q`.
+    pub(crate) fn prove_with_exponent(
+        lambda: &BigInt,
+        statement: &RingPedersenStatement<E, H>,
+    ) -> RingPedersenProof<E, H> {
+        // 1. Sample alphas from 0 -> m from \phi(N), and commit A_i = T^{a_i} mod N.
+        let mut a = Vec::with_capacity(crate::M_SECURITY);
+        let mut A = Vec::with_capacity(crate::M_SECURITY);
+        for _ in 0..crate::M_SECURITY {
             // TODO: Consider ensuring we get a unit element of this subgroup
             let a_i = BigInt::sample_below(&statement.phi);
-            a[i] = a_i;
             let A_i = BigInt::mod_pow(&statement.T, &a_i, &statement.N);
-            A[i] = A_i;
-            hash.chain_bigint(&A_i);
+            a.push(a_i);
+            A.push(A_i);
         }
 
-        let e: BigInt = hash.result_bigint();
-        let bitwise_e: BitVec = BitVec::from(e.to_bytes().as_bits());
+        let bitwise_e = Self::challenge_bits(&A, statement);
 
-        let Z = [(); crate::M_SECURITY].map(|_| BigInt::zero());
+        let mut Z = Vec::with_capacity(crate::M_SECURITY);
         for i in 0..crate::M_SECURITY {
             let e_i = if bitwise_e[i] { BigInt::one() } else { BigInt::zero() };
-            let z_i = BigInt::mod_add(&a[i], &(e_i * witness.lambda), &statement.phi);
-            Z[i] = z_i;
+            let z_i = BigInt::mod_add(&a[i], &(e_i * lambda), &statement.phi);
+            Z.push(z_i);
         }
 
         Self {
@@ -108,21 +128,98 @@ impl<E: Curve, H: Digest + Clone> RingPedersenProof<E, H> {
         }
     }
 
-    pub fn verify(proof: &RingPedersenProof<E, H>, statement: &RingPedersenStatement<E, H>) -> FsDkrResult<()>{
+    /// Derives the Fiat-Shamir challenge bits by hashing the first-round commitments together
+    /// with the statement, binding the challenge to the transcript.
+    fn challenge_bits(a: &[BigInt], statement: &RingPedersenStatement<E, H>) -> BitVec {
+        let mut hash = H::new();
+        for a_i in a.iter() {
+            hash = hash.chain_bigint(a_i);
+        }
+        hash = hash
+            .chain_bigint(&statement.S)
+            .chain_bigint(&statement.T)
+            .chain_bigint(&statement.N);
+        let e: BigInt = hash.result_bigint();
+        BitVec::from(e.to_bytes().as_bits())
+    }
+
+    /// Verifies the proof for the party at `party_index`, returning
+    /// [FsDkrError::RingPedersenProofValidationError] (tagged with that index) on the first
+    /// mismatching check. The challenge is always recomputed from the transcript; the
+    /// prover-supplied `bitwise_e` is never trusted.
+    pub fn verify(
+        proof: &RingPedersenProof<E, H>,
+        statement: &RingPedersenStatement<E, H>,
+        party_index: u16,
+    ) -> FsDkrResult<()> {
+        if proof.A.len() != crate::M_SECURITY || proof.Z.len() != crate::M_SECURITY {
+            return Err(FsDkrError::RingPedersenProofValidationError { party_index });
+        }
+
+        let bitwise_e = Self::challenge_bits(&proof.A, statement);
+
         for i in 0..crate::M_SECURITY {
-            let mut e_i = 0;
-            if proof.bitwise_e[i] {
-                e_i = 1;
-            }
+            let e_i = if bitwise_e[i] { BigInt::one() } else { BigInt::zero() };
+            let lhs = BigInt::mod_pow(&statement.T, &proof.Z[i], &statement.N);
+            let rhs = BigInt::mod_mul(
+                &proof.A[i],
+                &BigInt::mod_pow(&statement.S, &e_i, &statement.N),
+                &statement.N,
+            );
 
-            if BigInt::mod_pow(&statement.T, &proof.Z[i], &statement.N) == BigInt::mod_mul(&proof.A[i], &BigInt::mod_pow(&statement.S, e_i, &statement.N), &statement.N) {
-                
+            if lhs != rhs {
+                return Err(FsDkrError::RingPedersenProofValidationError { party_index });
             }
         }
+
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
-}
\ No newline at end of file
+    use super::{RingPedersenProof, RingPedersenStatement};
+    use curv::elliptic::curves::Secp256k1;
+    use curv::BigInt;
+    use sha2::Sha256;
+
+    type E = Secp256k1;
+    type H = Sha256;
+
+    #[test]
+    fn ring_pedersen_proof_round_trip() {
+        let (statement, witness) = RingPedersenStatement::<E, H>::generate();
+        let proof = RingPedersenProof::<E, H>::prove(&witness, &statement);
+
+        assert!(RingPedersenProof::verify(&proof, &statement, 1).is_ok());
+    }
+
+    #[test]
+    fn tampered_z_is_rejected() {
+        let (statement, witness) = RingPedersenStatement::<E, H>::generate();
+        let mut proof = RingPedersenProof::<E, H>::prove(&witness, &statement);
+
+        proof.Z[0] = &proof.Z[0] + BigInt::from(1);
+
+        assert!(RingPedersenProof::verify(&proof, &statement, 1).is_err());
+    }
+
+    #[test]
+    fn tampered_commitment_is_rejected() {
+        let (statement, witness) = RingPedersenStatement::<E, H>::generate();
+        let mut proof = RingPedersenProof::<E, H>::prove(&witness, &statement);
+
+        proof.A[0] = &proof.A[0] + BigInt::from(1);
+
+        assert!(RingPedersenProof::verify(&proof, &statement, 1).is_err());
+    }
+
+    #[test]
+    fn mismatched_statement_is_rejected() {
+        let (statement, witness) = RingPedersenStatement::<E, H>::generate();
+        let (other_statement, _) = RingPedersenStatement::<E, H>::generate();
+        let proof = RingPedersenProof::<E, H>::prove(&witness, &statement);
+
+        assert!(RingPedersenProof::verify(&proof, &other_statement, 1).is_err());
+    }
+}
@@ -0,0 +1,426 @@
+//! A synchronous, dealerless distributed key generation (in the spirit of hbbft's
+//! `SyncKeyGen`), bootstrapping the very first [LocalKey] a group of parties holds so that
+//! [crate::refresh_message] and [crate::add_party_message] have something to refresh/join
+//! without relying on a trusted dealer.
+//!
+//! The protocol runs in three rounds over a broadcast channel:
+//! 1. [round1]: every party broadcasts a [JoinMessage], contributing a Paillier `ek` and
+//!    ring-Pedersen parameters (reusing [crate::add_party_message::generate_dlog_statement_proofs]).
+//! 2. [round2]: once every party's round-1 message is known, each party samples its own
+//!    degree-`t` Feldman-VSS polynomial and encrypts a sub-share for every destination party
+//!    under that party's round-1 `ek`.
+//! 3. [collect]: every party verifies all round-2 commitments against the sub-share it
+//!    decrypts for itself and sums the accepted sub-shares into its final secret share.
+//!
+//! A party whose proofs or sub-share fail to verify is reported by `party_index`, following the
+//! same identifiable-aborts model as [crate::refresh_message] and [crate::add_party_message].
+
+use crate::add_party_message::JoinMessage;
+use crate::error::{FsDkrError, FsDkrResult};
+use crate::ring_pedersen_proof::RingPedersenProof;
+use curv::cryptographic_primitives::hashing::Digest;
+use curv::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use curv::elliptic::curves::{Curve, Point, Scalar};
+use curv::BigInt;
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::party_i::{Keys, SharedKeys};
+use multi_party_ecdsa::protocols::multi_party_ecdsa::gg_2020::state_machine::keygen::LocalKey;
+use paillier::{Decrypt, DecryptionKey, Encrypt, EncryptionKey, Paillier, RawCiphertext, RawPlaintext};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use zk_paillier::zkproofs::{CompositeDLogProof, DLogStatement, NiCorrectKeyProof};
+
+/// Round-2 broadcast: a party's Feldman-VSS commitment to its own contribution polynomial,
+/// plus one encrypted sub-share per destination party index (1-indexed).
+#[derive(Clone, Deserialize, Serialize, Debug)]
+pub struct KeyGenMessage<E: Curve> {
+    pub(crate) party_index: u16,
+    pub(crate) points_committed_vec: Vec<Point<E>>,
+    pub(crate) sub_share_ciphertexts: Vec<BigInt>,
+}
+
+/// Round 1 of the dealerless keygen: broadcast a [JoinMessage] carrying this party's Paillier
+/// `ek`/correctness proof and ring-Pedersen parameters, tagged with its (pre-agreed) index.
+pub fn round1<E, H>(party_index: u16) -> (JoinMessage<E, H>, Keys)
+where
+    E: Curve,
+    H: Digest + Clone,
+{
+    let (mut join_message, keys) = JoinMessage::<E, H>::distribute();
+    join_message.set_party_index(party_index);
+    (join_message, keys)
+}
+
+/// Round 2: once every party's round-1 message is known, sample a fresh degree-`t` Feldman-VSS
+/// polynomial and encrypt a sub-share of it for every destination party in `destination_eks`
+/// (indexed the same way, i.e. `destination_eks[i]` belongs to party index `i + 1`).
+pub fn round2<E: Curve>(
+    party_index: u16,
+    t: u16,
+    destination_eks: &[EncryptionKey],
+) -> KeyGenMessage<E> {
+    let n = destination_eks.len() as u16;
+    let secret = Scalar::<E>::random();
+    let (vss_scheme, secret_shares) = VerifiableSS::<E>::share(t, n, &secret);
+
+    let sub_share_ciphertexts = destination_eks
+        .iter()
+        .enumerate()
+        .map(|(i, ek)| {
+            Paillier::encrypt(ek, RawPlaintext::from(secret_shares[i].to_bigint()))
+                .0
+                .into_owned()
+        })
+        .collect();
+
+    KeyGenMessage {
+        party_index,
+        points_committed_vec: vss_scheme.commitments,
+        sub_share_ciphertexts,
+    }
+}
+
+/// Round 3: verify every party's round-1 proofs and round-2 Feldman commitment, complaining with
+/// an identifiable-abort error tagged with the culprit's `party_index` about the first party that
+/// broke the protocol, then sum the accepted sub-shares and commitments into the group's very
+/// first [LocalKey].
+pub fn collect<E, H>(
+    party_index: u16,
+    t: u16,
+    n: u16,
+    dk: &DecryptionKey,
+    paillier_key_vec: Vec<EncryptionKey>,
+    h1_h2_n_tilde_vec: Vec<DLogStatement>,
+    round1_messages: &[JoinMessage<E, H>],
+    round2_messages: &[KeyGenMessage<E>],
+) -> FsDkrResult<LocalKey<E>>
+where
+    E: Curve,
+    H: Digest + Clone,
+{
+    for message in round1_messages.iter() {
+        let other_party_index = message.get_party_index()?;
+
+        if NiCorrectKeyProof::verify(&message.dk_correctness_proof, &message.ek, None).is_err() {
+            return Err(FsDkrError::PaillierKeyError {
+                party_index: other_party_index,
+            });
+        }
+
+        // The base-h2 proof was produced for the swapped statement (g = h2, ni = h1).
+        let dlog_statement_base_h2 = DLogStatement {
+            N: message.dlog_statement.N.clone(),
+            g: message.dlog_statement.ni.clone(),
+            ni: message.dlog_statement.g.clone(),
+        };
+        if CompositeDLogProof::verify(
+            &message.composite_dlog_proof_base_h1,
+            &message.dlog_statement,
+        )
+        .is_err()
+            || CompositeDLogProof::verify(
+                &message.composite_dlog_proof_base_h2,
+                &dlog_statement_base_h2,
+            )
+            .is_err()
+        {
+            return Err(FsDkrError::DlogProofValidationError {
+                party_index: other_party_index,
+            });
+        }
+
+        RingPedersenProof::verify(
+            &message.ring_pedersen_proof,
+            &message.ring_pedersen_statement,
+            other_party_index,
+        )?;
+    }
+
+    // Unlike the threshold schemes elsewhere in this crate, this is an additive scheme: every
+    // one of the n parties' round-2 contributions is required, so a missing or duplicated one
+    // must be reported rather than silently producing a LocalKey that disagrees with its peers.
+    let mut seen: HashMap<u16, u16> = HashMap::new();
+    for message in round2_messages.iter() {
+        *seen.entry(message.party_index).or_insert(0) += 1;
+    }
+    let mut colliding_indices: Vec<u16> = seen
+        .iter()
+        .filter(|(_, count)| **count > 1)
+        .map(|(index, _)| *index)
+        .collect();
+    if !colliding_indices.is_empty() {
+        colliding_indices.sort_unstable();
+        return Err(FsDkrError::PartyIndexCollisionError {
+            party_indices: colliding_indices,
+        });
+    }
+    if round2_messages.len() as u16 != n {
+        return Err(FsDkrError::InsufficientMessagesError {
+            received: round2_messages.len() as u16,
+            required: n,
+        });
+    }
+
+    let parameters = ShamirSecretSharing {
+        threshold: t,
+        share_count: n,
+    };
+
+    let mut new_share = Scalar::<E>::zero();
+    let mut pk_vec: Vec<Point<E>> = vec![Point::<E>::zero(); n as usize];
+    let mut y_sum_s = Point::<E>::zero();
+
+    for message in round2_messages.iter() {
+        let ciphertext = match message.sub_share_ciphertexts.get((party_index - 1) as usize) {
+            Some(ciphertext) => ciphertext.clone(),
+            None => {
+                return Err(FsDkrError::InvalidShareError {
+                    party_index: message.party_index,
+                })
+            }
+        };
+        let decrypted = Paillier::decrypt(dk, RawCiphertext::from(ciphertext))
+            .0
+            .into_owned();
+        let share = Scalar::<E>::from(&decrypted);
+
+        let commitment_scheme = VerifiableSS::<E> {
+            parameters: parameters.clone(),
+            commitments: message.points_committed_vec.clone(),
+        };
+        if commitment_scheme
+            .validate_share(&share, party_index)
+            .is_err()
+        {
+            return Err(FsDkrError::InvalidShareError {
+                party_index: message.party_index,
+            });
+        }
+
+        new_share = new_share + share;
+        y_sum_s = y_sum_s + &message.points_committed_vec[0];
+        // `points_committed_vec` holds Feldman coefficient commitments (degree t), not
+        // per-party point commitments, so sum each destination party's own point commitment
+        // (`get_point_commitment`) rather than the raw coefficients.
+        for (i, pk) in pk_vec.iter_mut().enumerate() {
+            *pk = pk.clone() + commitment_scheme.get_point_commitment((i + 1) as u16);
+        }
+    }
+
+    let keys_linear = SharedKeys {
+        x_i: new_share.clone(),
+        y: y_sum_s.clone(),
+    };
+
+    let vss_scheme = VerifiableSS::<E> {
+        parameters,
+        commitments: pk_vec.clone(),
+    };
+
+    Ok(LocalKey {
+        paillier_dk: dk.clone(),
+        pk_vec,
+        keys_linear,
+        paillier_key_vec,
+        y_sum_s,
+        h1_h2_n_tilde_vec,
+        vss_scheme,
+        i: party_index,
+        t,
+        n,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curv::elliptic::curves::Secp256k1;
+    use sha2::Sha256;
+
+    type E = Secp256k1;
+    type H = Sha256;
+
+    /// Runs round1/round2 for every party in a `t`-of-`n` group and returns the ingredients
+    /// needed to run `collect` (e.g. with a tampered message).
+    #[allow(clippy::type_complexity)]
+    fn run_rounds(
+        t: u16,
+        n: u16,
+    ) -> (
+        Vec<JoinMessage<E, H>>,
+        Vec<Keys>,
+        Vec<KeyGenMessage<E>>,
+        Vec<EncryptionKey>,
+        Vec<DLogStatement>,
+    ) {
+        let mut round1_messages = Vec::new();
+        let mut round1_keys = Vec::new();
+        for party_index in 1..=n {
+            let (join_message, keys) = round1::<E, H>(party_index);
+            round1_messages.push(join_message);
+            round1_keys.push(keys);
+        }
+
+        let paillier_key_vec: Vec<EncryptionKey> =
+            round1_keys.iter().map(|k| k.ek.clone()).collect();
+        let h1_h2_n_tilde_vec: Vec<DLogStatement> = round1_messages
+            .iter()
+            .map(|m| m.dlog_statement.clone())
+            .collect();
+
+        let round2_messages: Vec<_> = (1..=n)
+            .map(|party_index| round2::<E>(party_index, t, &paillier_key_vec))
+            .collect();
+
+        (
+            round1_messages,
+            round1_keys,
+            round2_messages,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+        )
+    }
+
+    #[test]
+    fn dealerless_keygen_round_trip_agrees_on_public_key() {
+        let (t, n) = (1, 3);
+        let (round1_messages, round1_keys, round2_messages, paillier_key_vec, h1_h2_n_tilde_vec) =
+            run_rounds(t, n);
+
+        let local_keys: Vec<_> = (1..=n)
+            .map(|party_index| {
+                collect::<E, H>(
+                    party_index,
+                    t,
+                    n,
+                    &round1_keys[(party_index - 1) as usize].dk,
+                    paillier_key_vec.clone(),
+                    h1_h2_n_tilde_vec.clone(),
+                    &round1_messages,
+                    &round2_messages,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        for local_key in local_keys.iter().skip(1) {
+            assert_eq!(local_key.y_sum_s, local_keys[0].y_sum_s);
+        }
+    }
+
+    #[test]
+    fn missing_round2_message_is_rejected() {
+        let (t, n) = (1, 3);
+        let (round1_messages, round1_keys, mut round2_messages, paillier_key_vec, h1_h2_n_tilde_vec) =
+            run_rounds(t, n);
+
+        round2_messages.pop();
+
+        let err = collect::<E, H>(
+            1,
+            t,
+            n,
+            &round1_keys[0].dk,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+            &round1_messages,
+            &round2_messages,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::InsufficientMessagesError {
+                received: 2,
+                required: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn colliding_round2_party_index_is_attributed_to_the_shared_index() {
+        let (t, n) = (1, 3);
+        let (round1_messages, round1_keys, mut round2_messages, paillier_key_vec, h1_h2_n_tilde_vec) =
+            run_rounds(t, n);
+
+        round2_messages[1].party_index = round2_messages[0].party_index;
+
+        let err = collect::<E, H>(
+            1,
+            t,
+            n,
+            &round1_keys[0].dk,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+            &round1_messages,
+            &round2_messages,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::PartyIndexCollisionError {
+                party_indices: vec![round2_messages[0].party_index],
+            }
+        );
+    }
+
+    #[test]
+    fn tampered_sub_share_ciphertext_is_attributed_to_the_sender() {
+        let (t, n) = (1, 3);
+        let (round1_messages, round1_keys, mut round2_messages, paillier_key_vec, h1_h2_n_tilde_vec) =
+            run_rounds(t, n);
+
+        // Party 2's sub-share meant for party 1 is replaced with an encryption of an unrelated
+        // value, so it no longer matches party 2's broadcast Feldman commitment.
+        let tampered_value = Scalar::<E>::random();
+        round2_messages[1].sub_share_ciphertexts[0] =
+            Paillier::encrypt(&paillier_key_vec[0], RawPlaintext::from(tampered_value.to_bigint()))
+                .0
+                .into_owned();
+
+        let err = collect::<E, H>(
+            1,
+            t,
+            n,
+            &round1_keys[0].dk,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+            &round1_messages,
+            &round2_messages,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::InvalidShareError {
+                party_index: round2_messages[1].party_index,
+            }
+        );
+    }
+
+    #[test]
+    fn short_sub_share_ciphertexts_is_rejected_instead_of_panicking() {
+        let (t, n) = (1, 3);
+        let (round1_messages, round1_keys, mut round2_messages, paillier_key_vec, h1_h2_n_tilde_vec) =
+            run_rounds(t, n);
+
+        round2_messages[1].sub_share_ciphertexts.pop();
+
+        let err = collect::<E, H>(
+            3,
+            t,
+            n,
+            &round1_keys[2].dk,
+            paillier_key_vec,
+            h1_h2_n_tilde_vec,
+            &round1_messages,
+            &round2_messages,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            FsDkrError::InvalidShareError {
+                party_index: round2_messages[1].party_index,
+            }
+        );
+    }
+}
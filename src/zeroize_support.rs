@@ -0,0 +1,39 @@
+//! A thin wrapper that overwrites secret material (decrypted shares, Paillier plaintexts) in
+//! place with a known-zero value when it goes out of scope. Only the value's own inline storage
+//! is overwritten: heap memory the old value owns (for example a `BigInt`'s digits) is released
+//! through that value's normal `Drop` and is not wiped.
+//!
+//! `curv`'s `Scalar<E>`/`Point<E>`/`BigInt` are foreign types that don't implement
+//! `zeroize::Zeroize`, and the orphan rule blocks fs-dkr from adding that impl itself, so instead
+//! of bounding on that trait, [Sensitive] is handed the value's own zero constructor and
+//! overwrites in place on drop.
+
+pub(crate) struct Sensitive<T> {
+    value: T,
+    zero: fn() -> T,
+}
+
+impl<T> Sensitive<T> {
+    pub(crate) fn new(value: T, zero: fn() -> T) -> Self {
+        Self { value, zero }
+    }
+
+    pub(crate) fn get(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Drop for Sensitive<T> {
+    fn drop(&mut self) {
+        // A plain `self.value = (self.zero)()` assignment is not a guaranteed wipe: nothing
+        // reads `self.value` again before `self` itself is deallocated, so the compiler is free
+        // to treat the store as dead and elide it under as-if rules -- exactly the failure mode
+        // `zeroize`'s volatile writes exist to prevent. Read the old value out so its resources
+        // are freed normally below, then overwrite the field through `write_volatile`, which the
+        // compiler must actually emit, and fence so it can't be reordered away either.
+        let stale = unsafe { std::ptr::read(&self.value) };
+        unsafe { std::ptr::write_volatile(&mut self.value, (self.zero)()) };
+        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        drop(stale);
+    }
+}